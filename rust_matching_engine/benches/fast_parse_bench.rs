@@ -0,0 +1,50 @@
+//! Compares the full `serde_json::from_str::<Order>` baseline against the
+//! actual `orders:new` hot path in `subscriber.rs` — `fast_parse::parse_envelope`
+//! followed by `OrderEnvelope::into_order` — rather than the envelope parse in
+//! isolation, since the envelope alone is never the whole story: every order
+//! submission pays for `into_order` too. Run with
+//! `cargo bench --bench fast_parse_bench` once this crate has a manifest
+//! wiring `criterion` as a dev-dependency and this file as a `[[bench]]`
+//! target.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_matching_engine::Order;
+
+const SAMPLE_ORDER_PAYLOAD: &str = r#"{
+    "id": "5f8a1b3c-2d4e-4f6a-8b9c-0d1e2f3a4b5c",
+    "side": "buy",
+    "symbol": "BTC-PERP",
+    "price": "64250.50",
+    "quantity": 10,
+    "timestamp": "2026-01-01T00:00:00Z"
+}"#;
+
+fn bench_full_order_parse(c: &mut Criterion) {
+    c.bench_function("serde_json::from_str::<Order>", |b| {
+        b.iter(|| {
+            let order: Order = serde_json::from_str(black_box(SAMPLE_ORDER_PAYLOAD)).unwrap();
+            black_box(order);
+        });
+    });
+}
+
+fn bench_envelope_into_order(c: &mut Criterion) {
+    c.bench_function("fast_parse::parse_envelope + into_order", |b| {
+        let mut interner = rust_matching_engine::fast_parse::SymbolInterner::new();
+        b.iter(|| {
+            // Mirrors `subscriber.rs`'s hot path end-to-end: borrow the
+            // envelope, intern the symbol, then build the same `Order` the
+            // matching engine consumes. `parse_envelope` alone is not what
+            // the hot path pays for — `into_order` is not optional.
+            let envelope =
+                rust_matching_engine::fast_parse::parse_envelope(black_box(SAMPLE_ORDER_PAYLOAD))
+                    .unwrap();
+            let symbol = interner.intern(envelope.symbol).to_string();
+            let order = envelope.into_order(symbol);
+            black_box(order);
+        });
+    });
+}
+
+criterion_group!(benches, bench_full_order_parse, bench_envelope_into_order);
+criterion_main!(benches);
@@ -1,35 +1,753 @@
 use tokio::sync::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::env;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use uuid::Uuid;
 
 use rust_matching_engine::{
-    Order, OrderBook, OrderStatus, BboUpdate, OrderBookSnapshot
+    fast_parse, Candle, MakerFill, OrderBook, OrderBookDelta, OrderStatus, BboUpdate, OrderBookSnapshot, Trade
 };
 
 use futures_util::stream::StreamExt;
+use futures_util::SinkExt;
 use redis::aio::ConnectionLike;
 use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
 
 const ORDER_SUBMIT_CHANNEL: &str = "orders:new";
+const ORDER_CANCEL_CHANNEL: &str = "orders:cancel";
+const ORDER_AMEND_CHANNEL: &str = "orders:amend";
+const ORACLE_UPDATE_CHANNEL: &str = "oracle:update";
 const ENGINE_CONTROL_CHANNEL: &str = "engine:control";
 const MARKET_EVENTS_CHANNEL: &str = "market:events";
 const TRADE_EXECUTION_CHANNEL: &str = "trades:executed";
 const ORDER_UPDATE_CHANNEL: &str = "orders:updated";
 const BBO_UPDATE_CHANNEL_PREFIX: &str = "marketdata:bbo:";
 const BOOK_SNAPSHOT_CHANNEL_PREFIX: &str = "marketdata:book:";
+const BOOK_DELTA_CHANNEL_PREFIX: &str = "marketdata:delta:";
+const CHECKPOINT_REQUEST_CHANNEL: &str = "marketdata:checkpoint:request";
+const ENGINE_ROLLOVER_CHANNEL: &str = "engine:rollover";
+const CANDLE_CHANNEL_PREFIX: &str = "marketdata:candle:";
 const SNAPSHOT_DEPTH: usize = 5;
+const WS_BIND_ADDR_DEFAULT: &str = "0.0.0.0:9001";
+
+/// Intervals the candle aggregator tracks per symbol, as (channel label,
+/// bucket width in seconds).
+const CANDLE_INTERVALS: &[(&str, u64)] = &[("1m", 60), ("5m", 300), ("1h", 3600)];
+
+/// How often the in-progress (not-yet-closed) candle for each symbol/interval
+/// is republished, so charts keep updating even when no trades occur.
+const CANDLE_TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Capacity of the bounded channel feeding the persistence task, so a slow
+/// or down Postgres never backs up into the hot matching path.
+const PERSISTENCE_CHANNEL_CAPACITY: usize = 4096;
+/// Largest batch the persistence task inserts in one flush.
+const PERSISTENCE_BATCH_MAX: usize = 200;
+/// Upper bound on how long a partial batch waits before being flushed.
+const PERSISTENCE_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the rollover scheduler checks whether any configured rule's
+/// weekday/hour has arrived.
+const ROLLOVER_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the expiry reaper sweeps every book for GTT orders whose
+/// `expires_at` has passed.
+const REAP_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 type OrderBookMap = Arc<Mutex<HashMap<String, OrderBook>>>;
 
+/// In-progress candle per (symbol, interval_secs), fed by the
+/// `trades:executed` stream and finalized/republished once a trade lands in
+/// the next bucket.
+type CandleMap = Arc<Mutex<HashMap<(String, u64), Candle>>>;
+
+/// Symbols that have rolled off and no longer accept new orders. Checked by
+/// the `ORDER_SUBMIT_CHANNEL` task before an order reaches the book.
+type ClosedSymbolSet = Arc<Mutex<HashSet<String>>>;
+
+/// One scheduled expiry/rollover: at the configured UTC weekday/hour,
+/// `symbol` stops accepting orders, its book is cleared and settled, and
+/// `next_symbol` opens to take over. Configured via the `ROLLOVER_SCHEDULE`
+/// env var as a JSON array.
+#[derive(Deserialize, Debug, Clone)]
+struct RolloverRule {
+    symbol: String,
+    next_symbol: String,
+    /// Three-letter UTC weekday name, e.g. "Fri" (matches `chrono::Weekday`'s
+    /// `Display` output).
+    weekday: String,
+    /// UTC hour-of-day, 0-23.
+    hour: u32,
+}
+
+/// Latest full snapshot published per symbol, kept alongside `order_books` so
+/// a late-joining consumer (WebSocket subscribe, or a
+/// `marketdata:checkpoint:request`) can be brought up to date immediately
+/// instead of waiting for the next change.
+type SnapshotMap = Arc<Mutex<HashMap<String, OrderBookSnapshot>>>;
+
 #[derive(Deserialize, Debug)]
 struct EngineControlCommand { command: String }
 
+#[derive(Deserialize, Debug)]
+struct CheckpointRequest { symbol: String }
+
 #[derive(Deserialize, Debug)]
 struct MarketEventPayload { symbol: String, percent_shift: f64 }
 
+#[derive(Deserialize, Debug)]
+struct CancelRequest { symbol: String, id: Uuid }
+
+#[derive(Deserialize, Debug)]
+struct AmendRequest {
+    symbol: String,
+    id: Uuid,
+    #[serde(default)]
+    new_price: Option<Decimal>,
+    #[serde(default)]
+    new_qty: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OracleUpdateRequest {
+    symbol: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    price: Decimal,
+}
+
+/// A connected WebSocket client and the set of market symbols it has asked
+/// to be kept up to date on.
+struct Peer {
+    sender: UnboundedSender<Message>,
+    subscriptions: HashSet<String>,
+}
+
+type PeerMap = Arc<Mutex<HashMap<SocketAddr, Peer>>>;
+
+/// A durable record destined for Postgres, queued from the hot matching path
+/// and written by `run_persistence` without the submitter ever waiting on I/O.
+#[derive(Debug, Clone)]
+enum PersistenceEvent {
+    Trade(Trade),
+    OrderUpdate {
+        order_id: Uuid,
+        status: OrderStatus,
+        remaining_quantity: Option<u64>,
+        recorded_at: DateTime<Utc>,
+    },
+}
+
+/// `None` when `DATABASE_URL` isn't set, so persistence stays fully optional.
+type PersistenceSender = Option<mpsc::Sender<PersistenceEvent>>;
+
+/// Queues `event` for durable storage without blocking the caller. Drops the
+/// event (with a warning) if the persistence task is down or backlogged.
+fn try_persist(tx: &PersistenceSender, event: PersistenceEvent) {
+    if let Some(tx) = tx {
+        if let Err(e) = tx.try_send(event) {
+            log::warn!("Persistence channel unavailable, dropping event: {}", e);
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "command", rename_all = "lowercase")]
+enum WsCommand {
+    Subscribe {
+        #[serde(rename = "marketId")]
+        market_id: String,
+    },
+    Unsubscribe {
+        #[serde(rename = "marketId")]
+        market_id: String,
+    },
+    GetMarkets,
+}
+
+/// Floors `ts` to the start of its `interval_secs`-wide bucket.
+fn bucket_start(ts: DateTime<Utc>, interval_secs: u64) -> DateTime<Utc> {
+    let interval = interval_secs as i64;
+    let bucket_epoch = ts.timestamp().div_euclid(interval) * interval;
+    DateTime::from_timestamp(bucket_epoch, 0).unwrap_or(ts)
+}
+
+/// Publishes `candle` to `marketdata:candle:<interval_label>:<symbol>` and to
+/// any WebSocket peer subscribed to its symbol.
+async fn publish_candle(
+    conn: &mut MultiplexedConnection,
+    peers: &PeerMap,
+    interval_label: &str,
+    candle: &Candle,
+) {
+    if let Ok(json) = serde_json::to_string(candle) {
+        let chan = format!("{}{}:{}", CANDLE_CHANNEL_PREFIX, interval_label, candle.symbol);
+        let _ = conn
+            .publish::<_, _, ()>(&chan, &json)
+            .await
+            .map_err(|e| log::error!("FAIL Pub Candle {}: {}", chan, e));
+        broadcast_to_subscribers(peers, &candle.symbol, &json).await;
+    }
+}
+
+/// Folds one trade into every tracked interval's in-progress candle for its
+/// symbol, finalizing and publishing any bucket the trade has crossed out of.
+async fn accumulate_trade_into_candles(
+    candles: &CandleMap,
+    conn: &mut MultiplexedConnection,
+    peers: &PeerMap,
+    trade: &Trade,
+) {
+    for &(label, interval_secs) in CANDLE_INTERVALS {
+        let bucket = bucket_start(trade.timestamp, interval_secs);
+        let key = (trade.symbol.clone(), interval_secs);
+        let closed = {
+            let mut guard = candles.lock().await;
+            match guard.get_mut(&key) {
+                Some(candle) if candle.open_time == bucket => {
+                    candle.accumulate(trade.price, trade.quantity);
+                    None
+                }
+                Some(candle) => {
+                    let closed = candle.clone();
+                    *candle = Candle::open(trade.symbol.clone(), interval_secs, bucket, trade.price, trade.quantity);
+                    Some(closed)
+                }
+                None => {
+                    guard.insert(key, Candle::open(trade.symbol.clone(), interval_secs, bucket, trade.price, trade.quantity));
+                    None
+                }
+            }
+        };
+        if let Some(closed) = closed {
+            publish_candle(conn, peers, label, &closed).await;
+        }
+    }
+}
+
+/// Republishes every in-progress candle on a fixed tick so charts keep
+/// updating even when the matching market has no trades.
+async fn run_candle_ticker(candles: CandleMap, peers: PeerMap, mut conn: MultiplexedConnection) {
+    let mut ticker = tokio::time::interval(CANDLE_TICK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let in_progress: Vec<Candle> = candles.lock().await.values().cloned().collect();
+        for candle in in_progress {
+            if let Some((label, _)) = CANDLE_INTERVALS.iter().find(|(_, secs)| *secs == candle.interval_secs) {
+                publish_candle(&mut conn, &peers, label, &candle).await;
+            }
+        }
+    }
+}
+
+/// Connects to Postgres, ensures the `trades`/`order_updates` tables exist,
+/// then batches queued `PersistenceEvent`s into it: a batch is flushed once
+/// it hits `PERSISTENCE_BATCH_MAX` or `PERSISTENCE_FLUSH_INTERVAL` elapses,
+/// whichever comes first. Rows are timestamped and symbol-indexed so the
+/// candle service (or external analytics) can backfill history after a
+/// restart instead of losing it to `reset_engine`.
+async fn run_persistence(
+    database_url: String,
+    mut rx: mpsc::Receiver<PersistenceEvent>,
+) -> Result<(), tokio_postgres::Error> {
+    let (client, connection) = tokio_postgres::connect(&database_url, tokio_postgres::NoTls).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            log::error!("Postgres connection error: {}", e);
+        }
+    });
+
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS trades (
+                trade_id UUID PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                price TEXT NOT NULL,
+                quantity BIGINT NOT NULL,
+                taker_order_id UUID NOT NULL,
+                maker_order_id UUID NOT NULL,
+                executed_at TIMESTAMPTZ NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS trades_symbol_executed_at_idx ON trades (symbol, executed_at);
+
+            CREATE TABLE IF NOT EXISTS order_updates (
+                id BIGSERIAL PRIMARY KEY,
+                order_id UUID NOT NULL,
+                status TEXT NOT NULL,
+                remaining_quantity BIGINT,
+                recorded_at TIMESTAMPTZ NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS order_updates_order_id_idx ON order_updates (order_id, recorded_at);",
+        )
+        .await?;
+    log::info!("Persistence schema ready.");
+
+    let mut batch = Vec::with_capacity(PERSISTENCE_BATCH_MAX);
+    let mut flush_interval = tokio::time::interval(PERSISTENCE_FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            maybe_event = rx.recv() => {
+                match maybe_event {
+                    Some(event) => {
+                        batch.push(event);
+                        if batch.len() >= PERSISTENCE_BATCH_MAX {
+                            flush_persistence_batch(&client, &mut batch).await;
+                        }
+                    }
+                    None => {
+                        flush_persistence_batch(&client, &mut batch).await;
+                        log::warn!("Persistence channel closed; persistence task stopping.");
+                        return Ok(());
+                    }
+                }
+            }
+            _ = flush_interval.tick() => {
+                flush_persistence_batch(&client, &mut batch).await;
+            }
+        }
+    }
+}
+
+async fn flush_persistence_batch(client: &tokio_postgres::Client, batch: &mut Vec<PersistenceEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+    for event in batch.drain(..) {
+        let result = match &event {
+            PersistenceEvent::Trade(trade) => {
+                client
+                    .execute(
+                        "INSERT INTO trades (trade_id, symbol, price, quantity, taker_order_id, maker_order_id, executed_at)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7)
+                         ON CONFLICT (trade_id) DO NOTHING",
+                        &[
+                            &trade.trade_id,
+                            &trade.symbol,
+                            &trade.price.to_string(),
+                            &(trade.quantity as i64),
+                            &trade.taker_order_id,
+                            &trade.maker_order_id,
+                            &trade.timestamp,
+                        ],
+                    )
+                    .await
+            }
+            PersistenceEvent::OrderUpdate { order_id, status, remaining_quantity, recorded_at } => {
+                client
+                    .execute(
+                        "INSERT INTO order_updates (order_id, status, remaining_quantity, recorded_at)
+                         VALUES ($1, $2, $3, $4)",
+                        &[
+                            order_id,
+                            &format!("{:?}", status),
+                            &remaining_quantity.map(|q| q as i64),
+                            recorded_at,
+                        ],
+                    )
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            log::error!("Persistence write failed: {}", e);
+        }
+    }
+}
+
+/// Persists and publishes a single order's status transition on
+/// `orders:updated`. Shared by the taker (the order on `orders:new`) and
+/// every maker order a trade touched, so every status transition a trade
+/// produces reaches `order_updates`, not just the taker's.
+async fn publish_order_update(
+    order_id: Uuid,
+    status: OrderStatus,
+    remaining_quantity: Option<u64>,
+    persistence_tx: &PersistenceSender,
+    conn: &mut MultiplexedConnection,
+) {
+    try_persist(
+        persistence_tx,
+        PersistenceEvent::OrderUpdate {
+            order_id,
+            status,
+            remaining_quantity,
+            recorded_at: Utc::now(),
+        },
+    );
+    let update_payload = serde_json::json!({
+        "id": order_id,
+        "status": status,
+        "remaining_quantity": remaining_quantity,
+    });
+    if let Ok(json) = serde_json::to_string(&update_payload) {
+        let _ = conn
+            .publish::<_, _, ()>(ORDER_UPDATE_CHANNEL, &json)
+            .await
+            .map_err(|e| log::error!("FAIL Pub OrderUp {}: {}", order_id, e));
+    }
+}
+
+/// Republishes `book`'s BBO and depth snapshot if either changed, mirroring
+/// what `ORDER_SUBMIT_CHANNEL` does after `add_order` — shared so every other
+/// mutation path (cancel, amend, oracle re-peg, expiry reaping) keeps
+/// consumers of `marketdata:bbo:`/`marketdata:book:` in sync too.
+async fn publish_bbo_and_snapshot_if_changed(
+    book: &mut OrderBook,
+    peers: &PeerMap,
+    snapshots: &SnapshotMap,
+    conn: &mut MultiplexedConnection,
+) {
+    let (bid_p, bid_q, ask_p, ask_q) = book.get_bbo_with_qty();
+    let mut current_bbo = BboUpdate::new(book.symbol().to_string(), bid_p, bid_q, ask_p, ask_q);
+    // Compare prices and quantities only: `timestamp` and `seq` differ on
+    // every freshly built value.
+    let bbo_changed = book.last_bbo().as_ref().map_or(true, |last| {
+        (last.bid_price, last.bid_qty, last.ask_price, last.ask_qty)
+            != (current_bbo.bid_price, current_bbo.bid_qty, current_bbo.ask_price, current_bbo.ask_qty)
+    });
+    if bbo_changed {
+        current_bbo.seq = book.next_bbo_seq();
+        *book.last_bbo_mut() = Some(current_bbo.clone());
+        if let Ok(json) = serde_json::to_string(&current_bbo) {
+            let ch = format!("{}{}", BBO_UPDATE_CHANNEL_PREFIX, current_bbo.symbol);
+            let _ = conn
+                .publish::<_, _, ()>(&ch, &json)
+                .await
+                .map_err(|e| log::error!("FAIL Pub BBO {}: {}", current_bbo.symbol, e));
+            broadcast_to_subscribers(peers, &current_bbo.symbol, &json).await;
+        }
+    }
+
+    let mut current_snapshot = book.get_snapshot(SNAPSHOT_DEPTH);
+    let snapshot_changed = book.last_snapshot().as_ref().map_or(true, |last| {
+        last.bids != current_snapshot.bids || last.asks != current_snapshot.asks
+    });
+    if snapshot_changed {
+        current_snapshot.seq = book.next_snapshot_seq();
+        *book.last_snapshot_mut() = Some(current_snapshot.clone());
+        snapshots.lock().await.insert(current_snapshot.symbol.clone(), current_snapshot.clone());
+        if let Ok(json) = serde_json::to_string(&current_snapshot) {
+            let ch = format!("{}{}", BOOK_SNAPSHOT_CHANNEL_PREFIX, current_snapshot.symbol);
+            let _ = conn
+                .publish::<_, _, ()>(&ch, &json)
+                .await
+                .map_err(|e| log::error!("FAIL Pub Snap {}: {}", current_snapshot.symbol, e));
+            broadcast_to_subscribers(peers, &current_snapshot.symbol, &json).await;
+        }
+    }
+}
+
+/// Persists, publishes and broadcasts each `Trade`, and publishes a
+/// `MakerFill`-derived `OrderUpdate` for every maker it touched. Shared by
+/// every match-producing mutation path (`orders:new`, oracle re-peg), since
+/// each produces trades and maker fills the exact same way `add_order` does.
+async fn publish_trades_and_maker_fills(
+    trades: Vec<Trade>,
+    maker_fills: Vec<MakerFill>,
+    symbol: &str,
+    peers: &PeerMap,
+    persistence_tx: &PersistenceSender,
+    conn: &mut MultiplexedConnection,
+) {
+    for trade in trades {
+        log::info!(
+            "Pub Trade - Maker: {}, Taker: {}",
+            trade.maker_order_id,
+            trade.taker_order_id
+        );
+        try_persist(persistence_tx, PersistenceEvent::Trade(trade.clone()));
+        if let Ok(json) = serde_json::to_string(&trade) {
+            let _ = conn
+                .publish::<_, _, ()>(TRADE_EXECUTION_CHANNEL, &json)
+                .await
+                .map_err(|e| log::error!("FAIL Pub Trade {}: {}", trade.trade_id, e));
+            broadcast_to_subscribers(peers, symbol, &json).await;
+        }
+    }
+
+    for MakerFill { order_id, status, remaining_quantity } in maker_fills {
+        let update_remaining_quantity = if status == OrderStatus::Filled { Some(0) } else { Some(remaining_quantity) };
+        publish_order_update(order_id, status, update_remaining_quantity, persistence_tx, conn).await;
+    }
+}
+
+/// Publishes each `OrderBookDelta` on `marketdata:delta:<symbol>` so a
+/// replica can apply them incrementally after bootstrapping from
+/// `OrderBook::checkpoint`, instead of re-fetching a full snapshot on every
+/// change.
+async fn publish_deltas(deltas: &[OrderBookDelta], conn: &mut MultiplexedConnection) {
+    for delta in deltas {
+        if let Ok(json) = serde_json::to_string(delta) {
+            let chan = format!("{}{}", BOOK_DELTA_CHANNEL_PREFIX, delta.symbol);
+            let _ = conn
+                .publish::<_, _, ()>(&chan, &json)
+                .await
+                .map_err(|e| log::error!("FAIL Pub Delta {} seq {}: {}", delta.symbol, delta.seq, e));
+        }
+    }
+}
+
+/// Publishes a cleared (empty) BBO and snapshot for `symbol`, stamped with
+/// the given market-data sequence numbers. Shared by the `market:events`
+/// clear and the rollover settlement path, both of which clear a book and
+/// need consumers to see the same "book is now empty" announcement.
+async fn publish_cleared_market_data(
+    symbol: &str,
+    bbo_seq: u64,
+    snapshot_seq: u64,
+    conn: &mut MultiplexedConnection,
+    snapshots: &SnapshotMap,
+    peers: &PeerMap,
+) {
+    let mut cleared_bbo = BboUpdate::new(symbol.to_string(), None, None, None, None);
+    cleared_bbo.seq = bbo_seq;
+    if let Ok(bbo_json) = serde_json::to_string(&cleared_bbo) {
+        let chan = format!("{}{}", BBO_UPDATE_CHANNEL_PREFIX, symbol);
+        let _ = conn
+            .publish::<_, _, ()>(&chan, &bbo_json)
+            .await
+            .map_err(|e| log::error!("FAIL Pub CLEARED BBO {}: {}", symbol, e));
+        broadcast_to_subscribers(peers, symbol, &bbo_json).await;
+        log::info!("Pub CLEARED BBO for {}", symbol);
+    }
+
+    let mut cleared_snapshot = OrderBookSnapshot::new(symbol.to_string(), vec![], vec![]);
+    cleared_snapshot.seq = snapshot_seq;
+    snapshots.lock().await.insert(symbol.to_string(), cleared_snapshot.clone());
+    if let Ok(snap_json) = serde_json::to_string(&cleared_snapshot) {
+        let chan = format!("{}{}", BOOK_SNAPSHOT_CHANNEL_PREFIX, symbol);
+        let _ = conn
+            .publish::<_, _, ()>(&chan, &snap_json)
+            .await
+            .map_err(|e| log::error!("FAIL Pub CLEARED Snap {}: {}", symbol, e));
+        broadcast_to_subscribers(peers, symbol, &snap_json).await;
+        log::info!("Pub CLEARED Snapshot for {}", symbol);
+    }
+}
+
+/// Settles `rule.symbol` (clears and publishes the cleared book, closes it to
+/// new orders) and opens `rule.next_symbol`, then announces the mapping on
+/// `engine:rollover` so subscribers can migrate.
+async fn execute_rollover(
+    rule: &RolloverRule,
+    order_books: &OrderBookMap,
+    snapshots: &SnapshotMap,
+    peers: &PeerMap,
+    closed_symbols: &ClosedSymbolSet,
+    conn: &mut MultiplexedConnection,
+) {
+    log::warn!(">>> ROLLOVER: {} -> {} <<<", rule.symbol, rule.next_symbol);
+    closed_symbols.lock().await.insert(rule.symbol.clone());
+
+    let (bbo_seq, snapshot_seq) = {
+        let mut books_guard = order_books.lock().await;
+        let seqs = match books_guard.get_mut(&rule.symbol) {
+            Some(book) => {
+                book.clear_book();
+                (book.next_bbo_seq(), book.next_snapshot_seq())
+            }
+            None => (0, 0),
+        };
+        books_guard
+            .entry(rule.next_symbol.clone())
+            .or_insert_with(|| OrderBook::new(rule.next_symbol.clone()));
+        seqs
+    };
+
+    publish_cleared_market_data(&rule.symbol, bbo_seq, snapshot_seq, conn, snapshots, peers).await;
+
+    let rollover_event = serde_json::json!({
+        "old_symbol": rule.symbol,
+        "new_symbol": rule.next_symbol,
+        "at": Utc::now(),
+    });
+    if let Ok(json) = serde_json::to_string(&rollover_event) {
+        let _ = conn
+            .publish::<_, _, ()>(ENGINE_ROLLOVER_CHANNEL, &json)
+            .await
+            .map_err(|e| log::error!("FAIL Pub Rollover {}->{}: {}", rule.symbol, rule.next_symbol, e));
+    }
+    log::info!("Rollover complete: {} -> {}", rule.symbol, rule.next_symbol);
+}
+
+/// Polls `rules` once per `ROLLOVER_POLL_INTERVAL`, firing `execute_rollover`
+/// for any rule whose configured UTC weekday/hour has arrived and hasn't
+/// already fired today.
+async fn run_rollover_scheduler(
+    rules: Vec<RolloverRule>,
+    order_books: OrderBookMap,
+    snapshots: SnapshotMap,
+    peers: PeerMap,
+    closed_symbols: ClosedSymbolSet,
+    mut conn: MultiplexedConnection,
+) {
+    let mut last_fired: HashMap<String, chrono::NaiveDate> = HashMap::new();
+    let mut ticker = tokio::time::interval(ROLLOVER_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let now = Utc::now();
+        for rule in &rules {
+            if now.weekday().to_string() != rule.weekday || now.hour() != rule.hour {
+                continue;
+            }
+            let today = now.date_naive();
+            if last_fired.get(&rule.symbol) == Some(&today) {
+                continue;
+            }
+            last_fired.insert(rule.symbol.clone(), today);
+            execute_rollover(rule, &order_books, &snapshots, &peers, &closed_symbols, &mut conn).await;
+        }
+    }
+}
+
+/// Sweeps every book once per `REAP_POLL_INTERVAL`, evicting GTT orders past
+/// their `expires_at` via `OrderBook::reap_expired` and publishing an
+/// `OrderUpdate` (status `Cancelled`) for each one plus a refreshed
+/// BBO/snapshot for any book the sweep actually touched, so resting orders
+/// with an expiry don't sit in the published book forever once they're gone.
+async fn run_expiry_reaper(
+    order_books: OrderBookMap,
+    snapshots: SnapshotMap,
+    peers: PeerMap,
+    persistence_tx: PersistenceSender,
+    mut conn: MultiplexedConnection,
+) {
+    let mut ticker = tokio::time::interval(REAP_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let now = Utc::now();
+        let mut books_guard = order_books.lock().await;
+        for book in books_guard.values_mut() {
+            let evicted = book.reap_expired(now);
+            if evicted.is_empty() {
+                continue;
+            }
+            publish_bbo_and_snapshot_if_changed(book, &peers, &snapshots, &mut conn).await;
+            for order in evicted {
+                publish_order_update(order.id, order.status, Some(order.remaining_quantity), &persistence_tx, &mut conn).await;
+            }
+        }
+    }
+}
+
+/// Pushes `payload` to every connected peer subscribed to `symbol`.
+async fn broadcast_to_subscribers(peers: &PeerMap, symbol: &str, payload: &str) {
+    let guard = peers.lock().await;
+    for peer in guard.values() {
+        if peer.subscriptions.contains(symbol) {
+            let _ = peer.sender.send(Message::Text(payload.to_string()));
+        }
+    }
+}
+
+/// Accepts WebSocket connections on `addr` for the lifetime of the process,
+/// spawning a task per peer. Runs alongside the Redis subscription loop so
+/// browser/TUI clients can stream BBO, book, and trade updates without
+/// needing a Redis connection of their own.
+async fn run_ws_gateway(
+    addr: String,
+    peers: PeerMap,
+    order_books: OrderBookMap,
+    snapshots: SnapshotMap,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+    log::info!("WebSocket gateway listening on {}", addr);
+    loop {
+        let (stream, peer_addr) = match listener.accept().await {
+            Ok(pair) => pair,
+            Err(e) => {
+                log::error!("WS accept failed: {}", e);
+                continue;
+            }
+        };
+        let peers = Arc::clone(&peers);
+        let order_books = Arc::clone(&order_books);
+        let snapshots = Arc::clone(&snapshots);
+        tokio::spawn(async move {
+            if let Err(e) = handle_ws_connection(stream, peer_addr, peers.clone(), order_books, snapshots).await {
+                log::warn!("WS connection {} ended with error: {}", peer_addr, e);
+            }
+            peers.lock().await.remove(&peer_addr);
+            log::info!("WS peer disconnected: {}", peer_addr);
+        });
+    }
+}
+
+async fn handle_ws_connection(
+    stream: TcpStream,
+    peer_addr: SocketAddr,
+    peers: PeerMap,
+    order_books: OrderBookMap,
+    snapshots: SnapshotMap,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ws_stream = tokio_tungstenite::accept_async(stream).await?;
+    let (mut ws_sink, mut ws_source) = ws_stream.split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+
+    peers.lock().await.insert(
+        peer_addr,
+        Peer { sender: tx, subscriptions: HashSet::new() },
+    );
+    log::info!("WS peer connected: {}", peer_addr);
+
+    let forward_task = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            if ws_sink.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(msg) = ws_source.next().await {
+        let msg = msg?;
+        if !msg.is_text() {
+            continue;
+        }
+        let text = msg.to_text()?;
+        match serde_json::from_str::<WsCommand>(text) {
+            Ok(WsCommand::Subscribe { market_id }) => {
+                if let Some(peer) = peers.lock().await.get_mut(&peer_addr) {
+                    peer.subscriptions.insert(market_id.clone());
+                }
+                log::info!("WS peer {} subscribed to {}", peer_addr, market_id);
+
+                if let Some(checkpoint) = snapshots.lock().await.get(&market_id) {
+                    if let Ok(json) = serde_json::to_string(checkpoint) {
+                        if let Some(peer) = peers.lock().await.get(&peer_addr) {
+                            let _ = peer.sender.send(Message::Text(json));
+                        }
+                    }
+                }
+            }
+            Ok(WsCommand::Unsubscribe { market_id }) => {
+                if let Some(peer) = peers.lock().await.get_mut(&peer_addr) {
+                    peer.subscriptions.remove(&market_id);
+                }
+                log::info!("WS peer {} unsubscribed from {}", peer_addr, market_id);
+            }
+            Ok(WsCommand::GetMarkets) => {
+                let markets: Vec<String> = order_books.lock().await.keys().cloned().collect();
+                if let Ok(json) = serde_json::to_string(&serde_json::json!({ "markets": markets })) {
+                    if let Some(peer) = peers.lock().await.get(&peer_addr) {
+                        let _ = peer.sender.send(Message::Text(json));
+                    }
+                }
+            }
+            Err(e) => log::warn!("WS bad command from {}: {}. Payload: {}", peer_addr, e, text),
+        }
+    }
+
+    forward_task.abort();
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> redis::RedisResult<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
@@ -52,8 +770,93 @@ async fn main() -> redis::RedisResult<()> {
     log::info!("Subscribed to: {}", ENGINE_CONTROL_CHANNEL);
     pubsub.subscribe(MARKET_EVENTS_CHANNEL).await?;
     log::info!("Subscribed to: {}", MARKET_EVENTS_CHANNEL);
+    pubsub.subscribe(CHECKPOINT_REQUEST_CHANNEL).await?;
+    log::info!("Subscribed to: {}", CHECKPOINT_REQUEST_CHANNEL);
+    pubsub.subscribe(TRADE_EXECUTION_CHANNEL).await?;
+    log::info!("Subscribed to: {}", TRADE_EXECUTION_CHANNEL);
+    pubsub.subscribe(ORDER_CANCEL_CHANNEL).await?;
+    log::info!("Subscribed to: {}", ORDER_CANCEL_CHANNEL);
+    pubsub.subscribe(ORDER_AMEND_CHANNEL).await?;
+    log::info!("Subscribed to: {}", ORDER_AMEND_CHANNEL);
+    pubsub.subscribe(ORACLE_UPDATE_CHANNEL).await?;
+    log::info!("Subscribed to: {}", ORACLE_UPDATE_CHANNEL);
 
     let order_books: OrderBookMap = Arc::new(Mutex::new(HashMap::new()));
+    let snapshots: SnapshotMap = Arc::new(Mutex::new(HashMap::new()));
+    let candles: CandleMap = Arc::new(Mutex::new(HashMap::new()));
+
+    let peers: PeerMap = Arc::new(Mutex::new(HashMap::new()));
+    let ws_bind_addr = env::var("WS_BIND_ADDR").unwrap_or_else(|_| WS_BIND_ADDR_DEFAULT.to_string());
+    let ws_peers = Arc::clone(&peers);
+    let ws_order_books = Arc::clone(&order_books);
+    let ws_snapshots = Arc::clone(&snapshots);
+    tokio::spawn(async move {
+        if let Err(e) = run_ws_gateway(ws_bind_addr, ws_peers, ws_order_books, ws_snapshots).await {
+            log::error!("WebSocket gateway exited: {}", e);
+        }
+    });
+
+    let ticker_candles = Arc::clone(&candles);
+    let ticker_peers = Arc::clone(&peers);
+    let ticker_conn = publish_conn.clone();
+    tokio::spawn(run_candle_ticker(ticker_candles, ticker_peers, ticker_conn));
+
+    let persistence_tx: PersistenceSender = match env::var("DATABASE_URL") {
+        Ok(database_url) => {
+            let (tx, rx) = mpsc::channel::<PersistenceEvent>(PERSISTENCE_CHANNEL_CAPACITY);
+            tokio::spawn(async move {
+                if let Err(e) = run_persistence(database_url, rx).await {
+                    log::error!("Persistence task exited: {}", e);
+                }
+            });
+            log::info!("Persistence enabled (DATABASE_URL set).");
+            Some(tx)
+        }
+        Err(_) => {
+            log::info!("DATABASE_URL not set; trade/order persistence disabled.");
+            None
+        }
+    };
+
+    let closed_symbols: ClosedSymbolSet = Arc::new(Mutex::new(HashSet::new()));
+    let rollover_rules: Vec<RolloverRule> = match env::var("ROLLOVER_SCHEDULE") {
+        Ok(raw) => match serde_json::from_str(&raw) {
+            Ok(rules) => rules,
+            Err(e) => {
+                log::error!("Failed to parse ROLLOVER_SCHEDULE: {}. Rollover disabled.", e);
+                Vec::new()
+            }
+        },
+        Err(_) => Vec::new(),
+    };
+    if rollover_rules.is_empty() {
+        log::info!("No rollover rules configured; rollover scheduler disabled.");
+    } else {
+        log::info!("Rollover scheduler enabled with {} rule(s).", rollover_rules.len());
+        let rollover_order_books = Arc::clone(&order_books);
+        let rollover_snapshots = Arc::clone(&snapshots);
+        let rollover_peers = Arc::clone(&peers);
+        let rollover_closed_symbols = Arc::clone(&closed_symbols);
+        let rollover_conn = publish_conn.clone();
+        tokio::spawn(run_rollover_scheduler(
+            rollover_rules,
+            rollover_order_books,
+            rollover_snapshots,
+            rollover_peers,
+            rollover_closed_symbols,
+            rollover_conn,
+        ));
+    }
+
+    let reap_order_books = Arc::clone(&order_books);
+    let reap_snapshots = Arc::clone(&snapshots);
+    let reap_peers = Arc::clone(&peers);
+    let reap_persistence_tx = persistence_tx.clone();
+    let reap_conn = publish_conn.clone();
+    tokio::spawn(run_expiry_reaper(reap_order_books, reap_snapshots, reap_peers, reap_persistence_tx, reap_conn));
+
+    let mut symbol_interner = fast_parse::SymbolInterner::new();
+
     let mut msg_stream = pubsub.on_message();
 
     log::info!("Entering main message processing loop...");
@@ -95,30 +898,25 @@ async fn main() -> redis::RedisResult<()> {
                     if let Some(book) = books_guard.get_mut(&event_data.symbol) {
                         log::info!("Applying market event (clearing book): {}", event_data.symbol);
                         book.clear_book();
+                        let cleared_bbo_seq = book.next_bbo_seq();
+                        let cleared_snapshot_seq = book.next_snapshot_seq();
 
                         let symbol_clone = event_data.symbol;
                         let mut publish_conn_clone = publish_conn.clone();
+                        let snapshots_clone = Arc::clone(&snapshots);
+                        let peers_clone = Arc::clone(&peers);
                         drop(books_guard);
 
                         tokio::spawn(async move {
-                            let cleared_bbo = BboUpdate::new(symbol_clone.clone(), None, None, None, None);
-                            if let Ok(bbo_json) = serde_json::to_string(&cleared_bbo) {
-                                let chan = format!("{}{}", BBO_UPDATE_CHANNEL_PREFIX, symbol_clone);
-                                let _ = publish_conn_clone
-                                    .publish(&chan, &bbo_json)
-                                    .await
-                                    .map_err(|e| log::error!("FAIL Pub CLEARED BBO {}: {}", symbol_clone, e));
-                                log::info!("Pub CLEARED BBO for {}", symbol_clone);
-                            }
-                            let cleared_snapshot = OrderBookSnapshot::new(symbol_clone.clone(), vec![], vec![]);
-                            if let Ok(snap_json) = serde_json::to_string(&cleared_snapshot) {
-                                let chan = format!("{}{}", BOOK_SNAPSHOT_CHANNEL_PREFIX, symbol_clone);
-                                let _ = publish_conn_clone
-                                    .publish::<_, _, ()>(&chan, &snap_json)
-                                    .await
-                                    .map_err(|e| log::error!("FAIL Pub CLEARED Snap {}: {}", symbol_clone, e));
-                                log::info!("Pub CLEARED Snapshot for {}", symbol_clone);
-                            }
+                            publish_cleared_market_data(
+                                &symbol_clone,
+                                cleared_bbo_seq,
+                                cleared_snapshot_seq,
+                                &mut publish_conn_clone,
+                                &snapshots_clone,
+                                &peers_clone,
+                            )
+                            .await;
                         });
                     } else {
                         log::warn!("Market event for unknown symbol: {}", event_data.symbol);
@@ -129,29 +927,237 @@ async fn main() -> redis::RedisResult<()> {
             continue;
         }
 
+        if channel_name == CHECKPOINT_REQUEST_CHANNEL {
+            match serde_json::from_str::<CheckpointRequest>(&payload) {
+                Ok(req) => {
+                    let checkpoint = snapshots.lock().await.get(&req.symbol).cloned();
+                    match checkpoint {
+                        Some(snapshot) => {
+                            log::info!("Serving checkpoint request for {}", req.symbol);
+                            if let Ok(json) = serde_json::to_string(&snapshot) {
+                                let ch = format!("{}{}", BOOK_SNAPSHOT_CHANNEL_PREFIX, req.symbol);
+                                let _ = publish_conn
+                                    .clone()
+                                    .publish::<_, _, ()>(&ch, &json)
+                                    .await
+                                    .map_err(|e| log::error!("FAIL Pub Checkpoint {}: {}", req.symbol, e));
+                                broadcast_to_subscribers(&peers, &req.symbol, &json).await;
+                            }
+
+                            // Also serve a `checkpoint()` on the delta channel
+                            // itself: a replica driven off `marketdata:delta:`
+                            // needs the full-depth snapshot *and* the delta
+                            // seq it was taken at, so it can bootstrap from
+                            // this and apply only deltas with seq > delta_seq.
+                            let full_checkpoint = order_books
+                                .lock()
+                                .await
+                                .get(&req.symbol)
+                                .map(OrderBook::checkpoint);
+                            if let Some((delta_seq, full_snapshot)) = full_checkpoint {
+                                let checkpoint_payload = serde_json::json!({
+                                    "delta_seq": delta_seq,
+                                    "snapshot": full_snapshot,
+                                });
+                                if let Ok(json) = serde_json::to_string(&checkpoint_payload) {
+                                    let ch = format!("{}{}", BOOK_DELTA_CHANNEL_PREFIX, req.symbol);
+                                    let _ = publish_conn
+                                        .clone()
+                                        .publish::<_, _, ()>(&ch, &json)
+                                        .await
+                                        .map_err(|e| log::error!("FAIL Pub Delta Checkpoint {}: {}", req.symbol, e));
+                                }
+                            }
+                        }
+                        None => log::warn!("Checkpoint requested for unknown symbol: {}", req.symbol),
+                    }
+                }
+                Err(e) => log::error!("Failed parse checkpoint request: {}. Payload: {}", e, payload),
+            }
+            continue;
+        }
+
+        if channel_name == TRADE_EXECUTION_CHANNEL {
+            match serde_json::from_str::<Trade>(&payload) {
+                Ok(trade) => {
+                    let candles_clone = Arc::clone(&candles);
+                    let peers_clone = Arc::clone(&peers);
+                    let mut publish_conn_clone = publish_conn.clone();
+                    tokio::spawn(async move {
+                        accumulate_trade_into_candles(&candles_clone, &mut publish_conn_clone, &peers_clone, &trade).await;
+                    });
+                }
+                Err(e) => log::error!("Failed parse trade for candle aggregation: {}. Payload: {}", e, payload),
+            }
+            continue;
+        }
+
+        if channel_name == ORDER_CANCEL_CHANNEL {
+            match serde_json::from_str::<CancelRequest>(&payload) {
+                Ok(req) => {
+                    let peers_clone = Arc::clone(&peers);
+                    let snapshots_clone = Arc::clone(&snapshots);
+                    let persistence_tx_clone = persistence_tx.clone();
+                    let mut publish_conn_clone = publish_conn.clone();
+                    let books_clone = Arc::clone(&order_books);
+                    tokio::spawn(async move {
+                        let mut books_guard = books_clone.lock().await;
+                        let Some(book) = books_guard.get_mut(&req.symbol) else {
+                            log::warn!("Cancel requested for unknown symbol: {}", req.symbol);
+                            return;
+                        };
+                        let (cancelled, deltas) = book.cancel_order(req.id);
+                        publish_deltas(&deltas, &mut publish_conn_clone).await;
+                        publish_bbo_and_snapshot_if_changed(book, &peers_clone, &snapshots_clone, &mut publish_conn_clone).await;
+                        match cancelled {
+                            Some(order) => {
+                                publish_order_update(
+                                    order.id,
+                                    order.status,
+                                    Some(order.remaining_quantity),
+                                    &persistence_tx_clone,
+                                    &mut publish_conn_clone,
+                                )
+                                .await;
+                            }
+                            None => log::warn!("Cancel requested for unknown order {} on {}", req.id, req.symbol),
+                        }
+                    });
+                }
+                Err(e) => log::error!("Failed parse cancel request: {}. Payload: {}", e, payload),
+            }
+            continue;
+        }
+
+        if channel_name == ORDER_AMEND_CHANNEL {
+            match serde_json::from_str::<AmendRequest>(&payload) {
+                Ok(req) => {
+                    let peers_clone = Arc::clone(&peers);
+                    let snapshots_clone = Arc::clone(&snapshots);
+                    let persistence_tx_clone = persistence_tx.clone();
+                    let mut publish_conn_clone = publish_conn.clone();
+                    let books_clone = Arc::clone(&order_books);
+                    tokio::spawn(async move {
+                        let mut books_guard = books_clone.lock().await;
+                        let Some(book) = books_guard.get_mut(&req.symbol) else {
+                            log::warn!("Amend requested for unknown symbol: {}", req.symbol);
+                            return;
+                        };
+                        let (amended, deltas) = book.amend_order(req.id, req.new_price, req.new_qty);
+                        publish_deltas(&deltas, &mut publish_conn_clone).await;
+                        publish_bbo_and_snapshot_if_changed(book, &peers_clone, &snapshots_clone, &mut publish_conn_clone).await;
+                        match amended {
+                            Some(order) => {
+                                publish_order_update(
+                                    order.id,
+                                    order.status,
+                                    Some(order.remaining_quantity),
+                                    &persistence_tx_clone,
+                                    &mut publish_conn_clone,
+                                )
+                                .await;
+                            }
+                            None => log::warn!("Amend rejected or order {} not found on {}", req.id, req.symbol),
+                        }
+                    });
+                }
+                Err(e) => log::error!("Failed parse amend request: {}. Payload: {}", e, payload),
+            }
+            continue;
+        }
+
+        if channel_name == ORACLE_UPDATE_CHANNEL {
+            match serde_json::from_str::<OracleUpdateRequest>(&payload) {
+                Ok(req) => {
+                    let peers_clone = Arc::clone(&peers);
+                    let snapshots_clone = Arc::clone(&snapshots);
+                    let persistence_tx_clone = persistence_tx.clone();
+                    let mut publish_conn_clone = publish_conn.clone();
+                    let books_clone = Arc::clone(&order_books);
+                    tokio::spawn(async move {
+                        let mut books_guard = books_clone.lock().await;
+                        let Some(book) = books_guard.get_mut(&req.symbol) else {
+                            log::warn!("Oracle update for unknown symbol: {}", req.symbol);
+                            return;
+                        };
+                        let (trades, deltas, maker_fills) = book.update_oracle(req.price);
+                        publish_deltas(&deltas, &mut publish_conn_clone).await;
+                        publish_bbo_and_snapshot_if_changed(book, &peers_clone, &snapshots_clone, &mut publish_conn_clone).await;
+                        publish_trades_and_maker_fills(
+                            trades,
+                            maker_fills,
+                            &req.symbol,
+                            &peers_clone,
+                            &persistence_tx_clone,
+                            &mut publish_conn_clone,
+                        )
+                        .await;
+                    });
+                }
+                Err(e) => log::error!("Failed parse oracle update: {}. Payload: {}", e, payload),
+            }
+            continue;
+        }
+
         if channel_name == ORDER_SUBMIT_CHANNEL {
-            let order_result = serde_json::from_str::<Order>(&payload);
-            let order: Order = match order_result {
-                Ok(mut o) => { o.ensure_remaining_quantity(); o },
+            // This is the only parse an order submission pays for: no
+            // second full `serde_json::from_str::<Order>` follows, since
+            // `envelope.into_order` builds the real `Order` directly from
+            // the borrowed fields below.
+            let envelope = match fast_parse::parse_envelope(&payload) {
+                Ok(env) => env,
                 Err(e) => {
-                    log::error!("Failed deserialize order: {}. Payload: {}", e, payload);
+                    log::error!("Failed to parse order: {}. Payload: {}", e, payload);
                     continue;
                 }
             };
+            let interned_symbol = symbol_interner.intern(envelope.symbol);
+
+            if closed_symbols.lock().await.contains(envelope.symbol) {
+                log::warn!("Rejecting order {} for closed/expired symbol: {}", envelope.id, envelope.symbol);
+                let update_payload = serde_json::json!({
+                    "id": envelope.id,
+                    "status": OrderStatus::Rejected,
+                    "remaining_quantity": envelope.quantity,
+                });
+                if let Ok(json) = serde_json::to_string(&update_payload) {
+                    let _ = publish_conn
+                        .clone()
+                        .publish::<_, _, ()>(ORDER_UPDATE_CHANNEL, &json)
+                        .await
+                        .map_err(|e| log::error!("FAIL Pub OrderUp (closed symbol) {}: {}", envelope.id, e));
+                }
+                continue;
+            }
+
+            let order = envelope.into_order(interned_symbol.to_string());
             log::info!("Deserialized order ID: {}", order.id);
 
             let books_clone = Arc::clone(&order_books);
+            let peers_clone = Arc::clone(&peers);
+            let snapshots_clone = Arc::clone(&snapshots);
+            let persistence_tx_clone = persistence_tx.clone();
             let order_id_for_task = order.id;
-            let symbol_for_task = order.symbol.clone();
+            let symbol_for_task = Arc::clone(&interned_symbol);
             let mut publish_conn_clone = publish_conn.clone();
 
             tokio::spawn(async move {
+                let symbol_for_broadcast = symbol_for_task.clone();
                 let mut books_guard = books_clone.lock().await;
-                let book = books_guard
-                    .entry(symbol_for_task.clone())
-                    .or_insert_with(|| OrderBook::new(symbol_for_task));
+                // Interning lets the common case (book already exists)
+                // look the book up by borrowed `&str`, with no `String`
+                // allocation; only a genuinely new symbol pays for one,
+                // to create the map entry and the book's own `symbol`.
+                let book = if let Some(existing) = books_guard.get_mut(symbol_for_task.as_ref()) {
+                    existing
+                } else {
+                    books_guard
+                        .entry(symbol_for_task.to_string())
+                        .or_insert_with(|| OrderBook::new(symbol_for_task.to_string()))
+                };
 
-                let (final_status, trades) = book.add_order(order);
+                let (final_status, trades, deltas, maker_fills) = book.add_order(order);
+                publish_deltas(&deltas, &mut publish_conn_clone).await;
                 log::info!(
                     "Order {} processed. Status: {:?}, Trades: {}",
                     order_id_for_task,
@@ -159,62 +1165,26 @@ async fn main() -> redis::RedisResult<()> {
                     trades.len()
                 );
 
-                let (bid_p, bid_q, ask_p, ask_q) = book.get_bbo_with_qty();
-                let current_bbo = BboUpdate::new(book.symbol().to_string(), bid_p, bid_q, ask_p, ask_q);
-                let bbo_changed = book.last_bbo().as_ref() != Some(&current_bbo);
-                if bbo_changed {
-                    *book.last_bbo_mut() = Some(current_bbo.clone());
-                    if let Ok(json) = serde_json::to_string(&current_bbo) {
-                        let ch = format!("{}{}", BBO_UPDATE_CHANNEL_PREFIX, current_bbo.symbol);
-                        let _ = publish_conn_clone
-                            .publish(&ch, &json)
-                            .await
-                            .map_err(|e| log::error!("FAIL Pub BBO {}: {}", current_bbo.symbol, e));
-                    }
-                }
-
-                let current_snapshot = book.get_snapshot(SNAPSHOT_DEPTH);
-                let snapshot_changed = book.last_snapshot().as_ref() != Some(&current_snapshot);
-                if snapshot_changed {
-                    *book.last_snapshot_mut() = Some(current_snapshot.clone());
-                    if let Ok(json) = serde_json::to_string(&current_snapshot) {
-                        let ch = format!("{}{}", BOOK_SNAPSHOT_CHANNEL_PREFIX, current_snapshot.symbol);
-                        let _ = publish_conn_clone
-                            .publish(&ch, &json)
-                            .await
-                            .map_err(|e| log::error!("FAIL Pub Snap {}: {}", current_snapshot.symbol, e));
-                    }
-                }
-
-                for trade in trades {
-                    log::info!(
-                        "Pub Trade - Maker: {}, Taker: {}",
-                        trade.maker_order_id,
-                        trade.taker_order_id
-                    );
-                    if let Ok(json) = serde_json::to_string(&trade) {
-                        let _ = publish_conn_clone
-                            .publish(TRADE_EXECUTION_CHANNEL, &json)
-                            .await
-                            .map_err(|e| log::error!("FAIL Pub Trade {}: {}", trade.trade_id, e));
-                    }
-                }
+                publish_bbo_and_snapshot_if_changed(book, &peers_clone, &snapshots_clone, &mut publish_conn_clone).await;
+                publish_trades_and_maker_fills(
+                    trades,
+                    maker_fills,
+                    &symbol_for_broadcast,
+                    &peers_clone,
+                    &persistence_tx_clone,
+                    &mut publish_conn_clone,
+                )
+                .await;
 
-                let update_payload = serde_json::json!({
-                    "id": order_id_for_task,
-                    "status": final_status,
-                    "remaining_quantity": if final_status == OrderStatus::Filled {
-                        Some(0)
-                    } else {
-                        None
-                    }
-                });
-                if let Ok(json) = serde_json::to_string(&update_payload) {
-                    let _ = publish_conn_clone
-                        .publish(ORDER_UPDATE_CHANNEL, &json)
-                        .await
-                        .map_err(|e| log::error!("FAIL Pub OrderUp {}: {}", order_id_for_task, e));
-                }
+                let update_remaining_quantity = if final_status == OrderStatus::Filled { Some(0) } else { None };
+                publish_order_update(
+                    order_id_for_task,
+                    final_status,
+                    update_remaining_quantity,
+                    &persistence_tx_clone,
+                    &mut publish_conn_clone,
+                )
+                .await;
             });
         } else {
             log::warn!("Msg on unhandled channel: {}", channel_name);
@@ -3,15 +3,38 @@ use rust_decimal_macros::dec;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderSide {
+    #[default]
     Buy,
     Sell,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderType {
+    #[default]
+    Limit,
+    Market,
+    /// Rests at a price anchored to the oracle: `oracle_price - peg_offset`
+    /// for a `peg_side` of `Buy`, `oracle_price + peg_offset` for `Sell`.
+    /// Re-priced on every `OrderBook::update_oracle` call. See
+    /// `Order::peg_side` and `Order::peg_offset`.
+    Pegged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeInForce {
+    #[default]
+    Gtc,
+    Ioc,
+    Fok,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
@@ -37,6 +60,26 @@ pub struct Order {
     pub status: OrderStatus,
     #[serde(default, alias = "quantity")]
     pub remaining_quantity: u64,
+    #[serde(default)]
+    pub order_type: OrderType,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    /// For `OrderType::Pegged` orders: which side of the oracle price this
+    /// order is anchored to — `Buy` rests at `oracle_price - peg_offset`,
+    /// `Sell` rests at `oracle_price + peg_offset` (see `peg_offset`).
+    /// Defaults to `Buy` if omitted. Ignored otherwise.
+    #[serde(default)]
+    pub peg_side: OrderSide,
+    /// For `OrderType::Pegged` orders: the (by convention, non-negative)
+    /// distance from the oracle price this order rests at, applied in the
+    /// direction `peg_side` anchors toward and clamped to the book's tick
+    /// size. Ignored otherwise.
+    #[serde(default)]
+    pub peg_offset: Decimal,
+    /// Good-till-time: once resting, this order is evicted by
+    /// `OrderBook::reap_expired` at or after this instant. `None` means GTC.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl Order {
@@ -66,6 +109,11 @@ impl Order {
             timestamp: Utc::now(),
             status: OrderStatus::New,
             remaining_quantity: quantity,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            peg_side: OrderSide::Buy,
+            peg_offset: Decimal::ZERO,
+            expires_at: None,
         }
     }
 }
@@ -103,6 +151,43 @@ impl Trade {
     }
 }
 
+/// One OHLCV bar for a (symbol, interval) bucket, built by the candle
+/// aggregation subsystem in `bin/subscriber.rs` from the `trades:executed`
+/// stream. `open_time` is the bucket start
+/// (`floor(trade_ts / interval_secs) * interval_secs`); the bar covers
+/// `[open_time, open_time + interval_secs)`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Candle {
+    pub symbol: String,
+    pub interval_secs: u64,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub open: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub high: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub low: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub close: Decimal,
+    pub volume: u64,
+    pub open_time: DateTime<Utc>,
+}
+
+impl Candle {
+    /// Opens a fresh bucket from a single trade, seeding OHLC with its price.
+    pub fn open(symbol: String, interval_secs: u64, open_time: DateTime<Utc>, price: Decimal, quantity: u64) -> Self {
+        Candle { symbol, interval_secs, open: price, high: price, low: price, close: price, volume: quantity, open_time }
+    }
+
+    /// Folds one more trade into this bucket: extends high/low, updates
+    /// close, and accumulates volume.
+    pub fn accumulate(&mut self, price: Decimal, quantity: u64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += quantity;
+    }
+}
+
 mod decimal_option_serde_as_string {
     use rust_decimal::Decimal;
     use serde::{Serializer, Deserializer, Deserialize};
@@ -131,6 +216,130 @@ mod decimal_option_serde_as_string {
     }
 }
 
+/// Allocation-light parsing for the highest-frequency message a subscriber
+/// sees: order submissions. `OrderEnvelope` mirrors every [`Order`] field,
+/// borrowing `symbol` directly out of the JSON buffer instead of allocating
+/// a `String` up front, and `into_order` builds the real `Order` straight
+/// from it. This is the only parse an order submission pays for — there is
+/// no second `serde_json::from_str::<Order>` once an envelope parses.
+pub mod fast_parse {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Utc};
+    use rust_decimal::Decimal;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    use super::{Order, OrderStatus, OrderSide, OrderType, TimeInForce};
+
+    /// Number of distinct symbols kept before the least-recently-used entry
+    /// is evicted. Generous for the handful of markets a single engine
+    /// instance trades.
+    const SYMBOL_CACHE_CAPACITY: usize = 256;
+
+    /// Borrowed mirror of [`Order`]: same fields, same defaults, but
+    /// `symbol` borrows directly out of the source payload instead of
+    /// allocating a `String` up front. This is the *only* parse an order
+    /// submission pays for — there is no second full-`Order` deserialize
+    /// once an envelope parses successfully; `into_order` builds the real
+    /// `Order` straight from it.
+    #[derive(Deserialize, Debug)]
+    pub struct OrderEnvelope<'a> {
+        pub id: Uuid,
+        pub side: OrderSide,
+        pub symbol: &'a str,
+        #[serde(with = "rust_decimal::serde::str")]
+        pub price: Decimal,
+        pub quantity: u64,
+        pub timestamp: DateTime<Utc>,
+        #[serde(default)]
+        pub status: OrderStatus,
+        #[serde(default, alias = "quantity")]
+        pub remaining_quantity: u64,
+        #[serde(default)]
+        pub order_type: OrderType,
+        #[serde(default)]
+        pub time_in_force: TimeInForce,
+        #[serde(default)]
+        pub peg_side: OrderSide,
+        #[serde(default)]
+        pub peg_offset: Decimal,
+        #[serde(default)]
+        pub expires_at: Option<DateTime<Utc>>,
+    }
+
+    impl<'a> OrderEnvelope<'a> {
+        /// Builds the full `Order` the matching engine needs. `symbol` is
+        /// supplied by the caller (typically the string of a `SymbolInterner`
+        /// handle) and used as-is instead of `self.symbol`. `Order::symbol`
+        /// is an owned `String`, so the caller still allocates it once per
+        /// message. Normalizes `remaining_quantity` exactly as
+        /// `Order::ensure_remaining_quantity` would.
+        pub fn into_order(self, symbol: String) -> Order {
+            let mut order = Order {
+                id: self.id,
+                side: self.side,
+                symbol,
+                price: self.price,
+                quantity: self.quantity,
+                timestamp: self.timestamp,
+                status: self.status,
+                remaining_quantity: self.remaining_quantity,
+                order_type: self.order_type,
+                time_in_force: self.time_in_force,
+                peg_side: self.peg_side,
+                peg_offset: self.peg_offset,
+                expires_at: self.expires_at,
+            };
+            order.ensure_remaining_quantity();
+            order
+        }
+    }
+
+    /// Parses every `Order` field out of `payload` in one pass, borrowing
+    /// `symbol`. Returns the `serde_json` error on malformed JSON, on a
+    /// missing or invalid field, or on a `symbol` containing JSON escape
+    /// sequences (which cannot be borrowed).
+    pub fn parse_envelope(payload: &str) -> serde_json::Result<OrderEnvelope<'_>> {
+        serde_json::from_str(payload)
+    }
+
+    /// LRU cache of interned symbol strings, so a hot parsing loop
+    /// allocates a new `Arc<str>` only the first time it sees a given
+    /// symbol rather than on every message. Not `Sync`; owning code should
+    /// keep one per single-threaded receive loop rather than sharing it
+    /// behind a lock.
+    pub struct SymbolInterner {
+        cache: lru::LruCache<String, Arc<str>>,
+    }
+
+    impl SymbolInterner {
+        pub fn new() -> Self {
+            Self {
+                cache: lru::LruCache::new(NonZeroUsize::new(SYMBOL_CACHE_CAPACITY).unwrap()),
+            }
+        }
+
+        /// Returns the interned handle for `symbol`, allocating a new
+        /// `Arc<str>` only on a cache miss.
+        pub fn intern(&mut self, symbol: &str) -> Arc<str> {
+            if let Some(existing) = self.cache.get(symbol) {
+                return Arc::clone(existing);
+            }
+            let interned: Arc<str> = Arc::from(symbol);
+            self.cache.put(symbol.to_string(), Arc::clone(&interned));
+            interned
+        }
+    }
+
+    impl Default for SymbolInterner {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BboUpdate {
     pub symbol: String,
@@ -141,6 +350,13 @@ pub struct BboUpdate {
     pub ask_price: Option<Decimal>,
     pub ask_qty: Option<u64>,
     pub timestamp: DateTime<Utc>,
+    /// Per-symbol monotonic sequence number for this BBO stream, stamped by
+    /// the publisher via `OrderBook::next_bbo_seq` just before this message
+    /// goes out. A consumer that sees a jump of more than one should
+    /// request a fresh checkpoint to resync. `0` means unstamped (e.g. a
+    /// locally-built value that was never published).
+    #[serde(default)]
+    pub seq: u64,
 }
 
 impl BboUpdate {
@@ -158,10 +374,37 @@ impl BboUpdate {
             ask_price,
             ask_qty: ask_qty.filter(|&q| q > 0),
             timestamp: Utc::now(),
+            seq: 0,
         }
     }
 }
 
+/// An incremental L2 update for a single price level: the new aggregate
+/// resting quantity at that price (`0` meaning the level was removed). A
+/// consumer that bootstraps from `OrderBook::checkpoint` at delta sequence
+/// `S` can apply every delta with `seq > S` in order to stay in sync.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrderBookDelta {
+    pub symbol: String,
+    pub seq: u64,
+    pub side: OrderSide,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    pub new_quantity: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A maker order's status transition after resting quantity was taken by an
+/// incoming order, so the caller can persist/publish it the same way it
+/// would the taker's own `OrderUpdate` — matching produces exactly one of
+/// these per maker order touched by a trade, not just the taker's.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MakerFill {
+    pub order_id: Uuid,
+    pub status: OrderStatus,
+    pub remaining_quantity: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PriceLevelInfo {
     #[serde(with = "rust_decimal::serde::str")]
@@ -175,16 +418,37 @@ pub struct OrderBookSnapshot {
     pub bids: Vec<PriceLevelInfo>,
     pub asks: Vec<PriceLevelInfo>,
     pub timestamp: DateTime<Utc>,
+    /// Per-symbol monotonic sequence number for this snapshot stream,
+    /// stamped by the publisher via `OrderBook::next_snapshot_seq` just
+    /// before this message goes out. See `BboUpdate::seq`.
+    #[serde(default)]
+    pub seq: u64,
 }
 
 impl OrderBookSnapshot {
     pub fn new(symbol: String, bids: Vec<PriceLevelInfo>, asks: Vec<PriceLevelInfo>) -> Self {
-        OrderBookSnapshot { symbol, bids, asks, timestamp: Utc::now() }
+        OrderBookSnapshot { symbol, bids, asks, timestamp: Utc::now(), seq: 0 }
     }
 }
 
 type PriceLevel = VecDeque<Order>;
 
+/// Per-symbol validation rules, mirroring exchange-style market setup
+/// (tick/lot/min-size filters). The default is permissive so existing
+/// callers and tests keep working without opting in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketConfig {
+    pub tick_size: Decimal,
+    pub lot_size: u64,
+    pub min_size: u64,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        MarketConfig { tick_size: dec!(0), lot_size: 1, min_size: 1 }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct OrderBook {
     symbol: String,
@@ -192,6 +456,25 @@ pub struct OrderBook {
     asks: BTreeMap<Decimal, PriceLevel>,
     last_bbo: Option<BboUpdate>,
     last_snapshot: Option<OrderBookSnapshot>,
+    // Maps a resting order's id to where it currently lives so cancel/amend
+    // don't need to scan every price level.
+    order_index: HashMap<Uuid, (OrderSide, Decimal)>,
+    config: MarketConfig,
+    // Monotonic counter stamped onto every emitted `OrderBookDelta`.
+    seq: u64,
+    // Monotonic counters stamped onto published `BboUpdate`s and
+    // `OrderBookSnapshot`s respectively, distinct from `seq` above (which
+    // tracks L2 deltas) and from each other, so a consumer subscribed to
+    // only one of `marketdata:bbo:<symbol>` / `marketdata:book:<symbol>`
+    // sees a gap-free sequence rather than one that jumps by two on every
+    // update. See `next_bbo_seq`/`next_snapshot_seq`.
+    bbo_seq: u64,
+    snapshot_seq: u64,
+    oracle_price: Option<Decimal>,
+    // Pegged orders whose re-pegged price would be at or below zero; they
+    // sit here (off the book) until a later oracle update re-peggs them back
+    // to a positive price.
+    parked_pegged: Vec<Order>,
 }
 
 impl OrderBook {
@@ -199,12 +482,54 @@ impl OrderBook {
         OrderBook { symbol, ..Default::default() }
     }
 
+    pub fn with_config(symbol: String, config: MarketConfig) -> Self {
+        OrderBook { symbol, config, ..Default::default() }
+    }
+
+    /// Validates a candidate price/quantity against this book's tick/lot/min
+    /// size rules. `Limit` orders are tick-checked; `Market` orders are not,
+    /// since their price is not user-specified.
+    fn validate_market_params(&self, order_type: OrderType, price: Decimal, quantity: u64) -> Option<&'static str> {
+        if order_type == OrderType::Limit
+            && self.config.tick_size > dec!(0)
+            && price % self.config.tick_size != dec!(0)
+        {
+            return Some("tick size violation");
+        }
+        if self.config.lot_size > 0 && quantity % self.config.lot_size != 0 {
+            return Some("lot size violation");
+        }
+        if quantity < self.config.min_size {
+            return Some("min size violation");
+        }
+        None
+    }
+
     pub fn symbol(&self) -> &str { &self.symbol }
     pub fn last_bbo(&self) -> &Option<BboUpdate> { &self.last_bbo }
     pub fn last_bbo_mut(&mut self) -> &mut Option<BboUpdate> { &mut self.last_bbo }
     pub fn last_snapshot(&self) -> &Option<OrderBookSnapshot> { &self.last_snapshot }
     pub fn last_snapshot_mut(&mut self) -> &mut Option<OrderBookSnapshot> { &mut self.last_snapshot }
 
+    /// Increments and returns this book's BBO sequence number, to be stamped
+    /// onto the next published `BboUpdate` for this symbol so a consumer of
+    /// just `marketdata:bbo:<symbol>` can detect a gap (a jump of more than
+    /// one) from a Redis reconnect or slow-consumer drop. Tracked separately
+    /// from `next_snapshot_seq` so a single-channel consumer doesn't see
+    /// seq jump by two on every update.
+    pub fn next_bbo_seq(&mut self) -> u64 {
+        self.bbo_seq += 1;
+        self.bbo_seq
+    }
+
+    /// Increments and returns this book's snapshot sequence number, to be
+    /// stamped onto the next published `OrderBookSnapshot` for this symbol.
+    /// See `next_bbo_seq`.
+    pub fn next_snapshot_seq(&mut self) -> u64 {
+        self.snapshot_seq += 1;
+        self.snapshot_seq
+    }
+
     pub fn get_bbo_with_qty(&self) -> (Option<Decimal>, Option<u64>, Option<Decimal>, Option<u64>) {
         let best_bid_price = self.bids.keys().last().cloned();
         let best_bid_qty = best_bid_price.and_then(|p| self.bids.get(&p).map(|level| level.iter().map(|o| o.remaining_quantity).sum()));
@@ -227,40 +552,118 @@ impl OrderBook {
         OrderBookSnapshot::new(self.symbol.clone(), bids_snapshot, asks_snapshot)
     }
 
+    /// Returns the current sequence number alongside a full-depth snapshot so
+    /// a replica can bootstrap, then apply every subsequent delta with
+    /// `seq > checkpoint().0`.
+    pub fn checkpoint(&self) -> (u64, OrderBookSnapshot) {
+        (self.seq, self.get_snapshot(usize::MAX))
+    }
+
+    /// Builds the `OrderBookDelta`s for a set of touched price levels,
+    /// stamping each with the next sequence number.
+    fn deltas_for(&mut self, side: OrderSide, prices: impl IntoIterator<Item = Decimal>) -> Vec<OrderBookDelta> {
+        let levels = match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        let updates: Vec<(Decimal, u64)> = prices
+            .into_iter()
+            .map(|price| {
+                let qty = levels
+                    .get(&price)
+                    .map(|level| level.iter().map(|o| o.remaining_quantity).sum())
+                    .unwrap_or(0);
+                (price, qty)
+            })
+            .collect();
+        updates
+            .into_iter()
+            .map(|(price, new_quantity)| {
+                self.seq += 1;
+                OrderBookDelta {
+                    symbol: self.symbol.clone(),
+                    seq: self.seq,
+                    side,
+                    price,
+                    new_quantity,
+                    timestamp: Utc::now(),
+                }
+            })
+            .collect()
+    }
+
     pub fn clear_book(&mut self) {
         log::warn!("Clearing all orders from book: {}", self.symbol);
         let bid_count: usize = self.bids.values().map(VecDeque::len).sum();
         let ask_count: usize = self.asks.values().map(VecDeque::len).sum();
         self.bids.clear();
         self.asks.clear();
+        self.order_index.clear();
         self.last_bbo = None;
         self.last_snapshot = None;
         log::info!("Book {} cleared. Removed {} bids, {} asks.", self.symbol, bid_count, ask_count);
     }
 
-    pub fn add_order(&mut self, mut order: Order) -> (OrderStatus, Vec<Trade>) {
+    /// Sums the resting quantity on the opposite side that `order` could
+    /// cross against, without mutating the book. Used for the FOK pre-scan.
+    fn crossable_quantity(&self, order: &Order) -> u64 {
+        let is_market = order.order_type == OrderType::Market;
+        match order.side {
+            OrderSide::Buy => self
+                .asks
+                .iter()
+                .take_while(|(&price, _)| is_market || price <= order.price)
+                .map(|(_, level)| level.iter().map(|o| o.remaining_quantity).sum::<u64>())
+                .sum(),
+            OrderSide::Sell => self
+                .bids
+                .iter()
+                .rev()
+                .take_while(|(&price, _)| is_market || price >= order.price)
+                .map(|(_, level)| level.iter().map(|o| o.remaining_quantity).sum::<u64>())
+                .sum(),
+        }
+    }
+
+    pub fn add_order(&mut self, mut order: Order) -> (OrderStatus, Vec<Trade>, Vec<OrderBookDelta>, Vec<MakerFill>) {
         order.ensure_remaining_quantity();
 
         if order.symbol != self.symbol {
             log::error!("Order Rejected (Symbol mismatch): {:?}", order);
             order.status = OrderStatus::Rejected;
-            return (order.status, vec![]);
+            return (order.status, vec![], vec![], vec![]);
         }
-        if order.price <= dec!(0) {
+        if order.order_type != OrderType::Market && order.price <= dec!(0) {
             log::error!("Order Rejected (Invalid price): {:?}", order);
             order.status = OrderStatus::Rejected;
-            return (order.status, vec![]);
+            return (order.status, vec![], vec![], vec![]);
         }
         if order.quantity == 0 {
             log::error!("Order Rejected (Zero quantity): {:?}", order);
             order.status = OrderStatus::Rejected;
-            return (order.status, vec![]);
+            return (order.status, vec![], vec![], vec![]);
+        }
+        if let Some(reason) = self.validate_market_params(order.order_type, order.price, order.quantity) {
+            log::error!("Order Rejected ({}): {:?}", reason, order);
+            order.status = OrderStatus::Rejected;
+            return (order.status, vec![], vec![], vec![]);
+        }
+        if order.expires_at.is_some_and(|t| t <= Utc::now()) {
+            log::warn!("Order Rejected (Already expired): {:?}", order.id);
+            order.status = OrderStatus::Rejected;
+            return (order.status, vec![], vec![], vec![]);
         }
 
         if order.status == OrderStatus::New {
             order.status = OrderStatus::Accepted;
         }
 
+        if order.time_in_force == TimeInForce::Fok && self.crossable_quantity(&order) < order.remaining_quantity {
+            log::warn!("Order Rejected (FOK cannot be fully filled): {:?}", order.id);
+            order.status = OrderStatus::Rejected;
+            return (order.status, vec![], vec![], vec![]);
+        }
+
         log::info!(
             "Processing order: Id={}, Side={:?}, Price={}, Qty={}, Rem={}",
             order.id,
@@ -272,13 +675,18 @@ impl OrderBook {
 
         let mut trades = Vec::new();
         let mut taker_final_status = order.status;
+        let mut deltas = Vec::new();
+        let mut maker_fills = Vec::new();
 
         match order.side {
             OrderSide::Buy => {
                 let mut asks_to_remove = Vec::new();
+                let mut filled_maker_ids = Vec::new();
+                let mut touched_ask_prices = Vec::new();
                 for (&ask_price, price_level) in self.asks.iter_mut() {
                     if order.remaining_quantity == 0 { break; }
-                    if ask_price > order.price { break; }
+                    if order.order_type != OrderType::Market && ask_price > order.price { break; }
+                    let mut level_changed = false;
                     for maker_order in price_level.iter_mut() {
                         if order.remaining_quantity == 0 { break; }
                         let trade_quantity = std::cmp::min(order.remaining_quantity, maker_order.remaining_quantity);
@@ -288,22 +696,38 @@ impl OrderBook {
                             maker_order.remaining_quantity -= trade_quantity;
                             maker_order.status = if maker_order.remaining_quantity == 0 { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
                             log::debug!("Maker ask {} status -> {:?}, Rem: {}", maker_order.id, maker_order.status, maker_order.remaining_quantity);
+                            maker_fills.push(MakerFill {
+                                order_id: maker_order.id,
+                                status: maker_order.status,
+                                remaining_quantity: maker_order.remaining_quantity,
+                            });
+                            if maker_order.status == OrderStatus::Filled {
+                                filled_maker_ids.push(maker_order.id);
+                            }
+                            level_changed = true;
                         }
                     }
+                    if level_changed {
+                        touched_ask_prices.push(ask_price);
+                    }
                     price_level.retain(|o| o.status != OrderStatus::Filled);
                     if price_level.is_empty() {
                         asks_to_remove.push(ask_price);
                     }
                 }
+                for id in filled_maker_ids {
+                    self.order_index.remove(&id);
+                }
                 for price in asks_to_remove {
                     self.asks.remove(&price);
                     log::debug!("Removed empty ask level: {}", price);
                 }
+                deltas.extend(self.deltas_for(OrderSide::Sell, touched_ask_prices));
 
                 if order.remaining_quantity == 0 {
                     taker_final_status = OrderStatus::Filled;
                     log::info!("Taker buy order {} fully filled.", order.id);
-                } else {
+                } else if matches!(order.order_type, OrderType::Limit | OrderType::Pegged) && order.time_in_force == TimeInForce::Gtc {
                     if order.remaining_quantity < order.quantity {
                         taker_final_status = OrderStatus::PartiallyFilled;
                     }
@@ -314,14 +738,35 @@ impl OrderBook {
                         order.remaining_quantity
                     );
                     order.status = taker_final_status;
-                    self.bids.entry(order.price).or_default().push_back(order);
+                    let resting_price = order.price;
+                    self.order_index.insert(order.id, (OrderSide::Buy, resting_price));
+                    self.bids.entry(resting_price).or_default().push_back(order);
+                    deltas.extend(self.deltas_for(OrderSide::Buy, [resting_price]));
+                } else {
+                    taker_final_status = if order.remaining_quantity == order.quantity {
+                        OrderStatus::Cancelled
+                    } else {
+                        OrderStatus::PartiallyFilled
+                    };
+                    order.status = taker_final_status;
+                    log::info!(
+                        "Discarding unfilled remainder of buy order {} ({:?}/{:?}). Status: {:?}, Rem: {}",
+                        order.id,
+                        order.order_type,
+                        order.time_in_force,
+                        taker_final_status,
+                        order.remaining_quantity
+                    );
                 }
             }
             OrderSide::Sell => {
                 let mut bids_to_remove = Vec::new();
+                let mut filled_maker_ids = Vec::new();
+                let mut touched_bid_prices = Vec::new();
                 for (&bid_price, price_level) in self.bids.iter_mut().rev() {
                     if order.remaining_quantity == 0 { break; }
-                    if bid_price < order.price { break; }
+                    if order.order_type != OrderType::Market && bid_price < order.price { break; }
+                    let mut level_changed = false;
                     for maker_order in price_level.iter_mut() {
                         if order.remaining_quantity == 0 { break; }
                         let trade_quantity = std::cmp::min(order.remaining_quantity, maker_order.remaining_quantity);
@@ -331,22 +776,38 @@ impl OrderBook {
                             maker_order.remaining_quantity -= trade_quantity;
                             maker_order.status = if maker_order.remaining_quantity == 0 { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
                             log::debug!("Maker bid {} status -> {:?}, Rem: {}", maker_order.id, maker_order.status, maker_order.remaining_quantity);
+                            maker_fills.push(MakerFill {
+                                order_id: maker_order.id,
+                                status: maker_order.status,
+                                remaining_quantity: maker_order.remaining_quantity,
+                            });
+                            if maker_order.status == OrderStatus::Filled {
+                                filled_maker_ids.push(maker_order.id);
+                            }
+                            level_changed = true;
                         }
                     }
+                    if level_changed {
+                        touched_bid_prices.push(bid_price);
+                    }
                     price_level.retain(|o| o.status != OrderStatus::Filled);
                     if price_level.is_empty() {
                         bids_to_remove.push(bid_price);
                     }
                 }
+                for id in filled_maker_ids {
+                    self.order_index.remove(&id);
+                }
                 for price in bids_to_remove {
                     self.bids.remove(&price);
                     log::debug!("Removed empty bid level: {}", price);
                 }
+                deltas.extend(self.deltas_for(OrderSide::Buy, touched_bid_prices));
 
                 if order.remaining_quantity == 0 {
                     taker_final_status = OrderStatus::Filled;
                     log::info!("Taker sell order {} fully filled.", order.id);
-                } else {
+                } else if matches!(order.order_type, OrderType::Limit | OrderType::Pegged) && order.time_in_force == TimeInForce::Gtc {
                     if order.remaining_quantity < order.quantity {
                         taker_final_status = OrderStatus::PartiallyFilled;
                     }
@@ -357,11 +818,260 @@ impl OrderBook {
                         order.remaining_quantity
                     );
                     order.status = taker_final_status;
-                    self.asks.entry(order.price).or_default().push_back(order);
+                    let resting_price = order.price;
+                    self.order_index.insert(order.id, (OrderSide::Sell, resting_price));
+                    self.asks.entry(resting_price).or_default().push_back(order);
+                    deltas.extend(self.deltas_for(OrderSide::Sell, [resting_price]));
+                } else {
+                    taker_final_status = if order.remaining_quantity == order.quantity {
+                        OrderStatus::Cancelled
+                    } else {
+                        OrderStatus::PartiallyFilled
+                    };
+                    order.status = taker_final_status;
+                    log::info!(
+                        "Discarding unfilled remainder of sell order {} ({:?}/{:?}). Status: {:?}, Rem: {}",
+                        order.id,
+                        order.order_type,
+                        order.time_in_force,
+                        taker_final_status,
+                        order.remaining_quantity
+                    );
+                }
+            }
+        }
+        (taker_final_status, trades, deltas, maker_fills)
+    }
+
+    fn cancel_order_inner(&mut self, id: Uuid) -> Option<(Order, OrderSide, Decimal)> {
+        let (side, price) = self.order_index.remove(&id)?;
+        let levels = match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let level = levels.get_mut(&price)?;
+        let pos = level.iter().position(|o| o.id == id)?;
+        let mut removed = level.remove(pos)?;
+        if level.is_empty() {
+            levels.remove(&price);
+        }
+        removed.status = OrderStatus::Cancelled;
+        Some((removed, side, price))
+    }
+
+    /// Removes a resting order by id in O(1) lookup + O(level-len) splice,
+    /// using `order_index` instead of scanning every price level. Returns the
+    /// delta for the level it was removed from (`new_quantity` of `0` if the
+    /// level is now empty).
+    pub fn cancel_order(&mut self, id: Uuid) -> (Option<Order>, Vec<OrderBookDelta>) {
+        match self.cancel_order_inner(id) {
+            Some((removed, side, price)) => {
+                log::info!("Cancelled order {} ({:?} @ {})", id, side, price);
+                let deltas = self.deltas_for(side, [price]);
+                (Some(removed), deltas)
+            }
+            None => (None, vec![]),
+        }
+    }
+
+    /// Amends the price and/or quantity of a resting order. Changing the
+    /// price, or increasing the quantity, loses time priority: the order is
+    /// removed and re-inserted at the tail of its (possibly new) level.
+    /// Reducing the quantity in place keeps the order's original priority.
+    /// Returns the deltas for every level touched (old and, if different,
+    /// new), same as `cancel_order`.
+    pub fn amend_order(
+        &mut self,
+        id: Uuid,
+        new_price: Option<Decimal>,
+        new_qty: Option<u64>,
+    ) -> (Option<Order>, Vec<OrderBookDelta>) {
+        let (side, old_price) = match self.order_index.get(&id) {
+            Some(&entry) => entry,
+            None => return (None, vec![]),
+        };
+        let levels = match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        let existing = match levels.get(&old_price).and_then(|level| level.iter().find(|o| o.id == id)) {
+            Some(existing) => existing,
+            None => return (None, vec![]),
+        };
+        let candidate_price = new_price.unwrap_or(old_price);
+        let candidate_qty = new_qty.unwrap_or(existing.remaining_quantity);
+        if self.validate_market_params(existing.order_type, candidate_price, candidate_qty).is_some() {
+            return (None, vec![]);
+        }
+
+        let levels = match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let Some(level) = levels.get_mut(&old_price) else { return (None, vec![]); };
+        let Some(pos) = level.iter().position(|o| o.id == id) else { return (None, vec![]); };
+        let Some(mut order) = level.remove(pos) else { return (None, vec![]); };
+        if level.is_empty() {
+            levels.remove(&old_price);
+        }
+
+        let mut loses_priority = false;
+        if let Some(price) = new_price {
+            if price != old_price {
+                loses_priority = true;
+            }
+            order.price = price;
+        }
+        if let Some(qty) = new_qty {
+            if qty > order.remaining_quantity {
+                loses_priority = true;
+            }
+            order.quantity = qty;
+            order.remaining_quantity = qty;
+        }
+
+        let levels = match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let new_price = order.price;
+        let new_level = levels.entry(new_price).or_default();
+        if loses_priority {
+            new_level.push_back(order.clone());
+        } else {
+            let insert_pos = pos.min(new_level.len());
+            new_level.insert(insert_pos, order.clone());
+        }
+        self.order_index.insert(id, (side, new_price));
+        log::info!(
+            "Amended order {} -> price={}, qty={} (priority {})",
+            id,
+            order.price,
+            order.remaining_quantity,
+            if loses_priority { "lost" } else { "kept" }
+        );
+        let touched_prices = if new_price == old_price { vec![old_price] } else { vec![old_price, new_price] };
+        let deltas = self.deltas_for(side, touched_prices);
+        (Some(order), deltas)
+    }
+
+    fn clamp_to_tick(&self, price: Decimal) -> Decimal {
+        if self.config.tick_size > dec!(0) {
+            (price / self.config.tick_size).floor() * self.config.tick_size
+        } else {
+            price
+        }
+    }
+
+    /// Re-prices every resting `Pegged` order (and any previously parked one)
+    /// to its `peg_side`-anchored offset from `price` (see
+    /// `Order::peg_side`), clamped to the tick size, losing time priority on
+    /// the move, then re-runs matching for any that now cross the opposite
+    /// book. Orders that would re-peg to a non-positive price are parked off
+    /// the book instead of being inserted.
+    /// Returns, in the same shape as `add_order`/`cancel_order`: trades
+    /// produced by any re-pegged order that now crosses, the L2 deltas for
+    /// every level touched (vacated old level and, if re-inserted, new
+    /// level), and a `MakerFill` for every resting order those trades
+    /// touched. Does not touch `last_bbo`/`last_snapshot` itself — same as
+    /// `add_order`, that's the publishing caller's job once it decides the
+    /// BBO/snapshot actually changed.
+    pub fn update_oracle(&mut self, price: Decimal) -> (Vec<Trade>, Vec<OrderBookDelta>, Vec<MakerFill>) {
+        self.oracle_price = Some(price);
+        let mut trades = Vec::new();
+        let mut deltas = Vec::new();
+        let mut maker_fills = Vec::new();
+
+        let pegged_ids: Vec<Uuid> = self
+            .bids
+            .values()
+            .chain(self.asks.values())
+            .flat_map(|level| level.iter())
+            .filter(|o| o.order_type == OrderType::Pegged)
+            .map(|o| o.id)
+            .collect();
+
+        for id in pegged_ids {
+            if let Some((mut order, side, old_price)) = self.cancel_order_inner(id) {
+                deltas.extend(self.deltas_for(side, [old_price]));
+                let repriced = self.repeg_or_park(&mut order, price);
+                if repriced {
+                    let (_, new_trades, new_deltas, new_fills) = self.add_order(order);
+                    trades.extend(new_trades);
+                    deltas.extend(new_deltas);
+                    maker_fills.extend(new_fills);
+                } else {
+                    self.parked_pegged.push(order);
                 }
             }
         }
-        (taker_final_status, trades)
+
+        let parked = std::mem::take(&mut self.parked_pegged);
+        for mut order in parked {
+            let repriced = self.repeg_or_park(&mut order, price);
+            if repriced {
+                let (_, new_trades, new_deltas, new_fills) = self.add_order(order);
+                trades.extend(new_trades);
+                deltas.extend(new_deltas);
+                maker_fills.extend(new_fills);
+            } else {
+                self.parked_pegged.push(order);
+            }
+        }
+
+        (trades, deltas, maker_fills)
+    }
+
+    /// Sets `order.price` to its re-pegged value and returns `true` if that
+    /// value is positive (safe to re-insert); returns `false` (order left
+    /// untouched) if it would price at or below zero and must be parked.
+    fn repeg_or_park(&self, order: &mut Order, oracle_price: Decimal) -> bool {
+        order.status = OrderStatus::Accepted;
+        let signed_offset = match order.peg_side {
+            OrderSide::Buy => -order.peg_offset,
+            OrderSide::Sell => order.peg_offset,
+        };
+        let candidate = self.clamp_to_tick(oracle_price + signed_offset);
+        if candidate <= dec!(0) {
+            log::warn!("Pegged order {} parked (re-peg price {} <= 0)", order.id, candidate);
+            false
+        } else {
+            order.price = candidate;
+            true
+        }
+    }
+
+    /// Walks both sides of the book evicting every resting order whose
+    /// `expires_at` is at or before `now`, marking it `Cancelled` and
+    /// dropping any price level left empty, then returns the evicted orders
+    /// so the caller can notify their owners. Intended to be driven by a
+    /// periodic sweep rather than called per-order; unlike `cancel_order` it
+    /// does not emit `OrderBookDelta`s, since a single sweep can touch many
+    /// levels at once and the caller is expected to republish BBO/snapshot
+    /// state afterwards.
+    pub fn reap_expired(&mut self, now: DateTime<Utc>) -> Vec<Order> {
+        let expired_ids: Vec<Uuid> = self
+            .bids
+            .values()
+            .chain(self.asks.values())
+            .flat_map(|level| level.iter())
+            .filter(|o| o.expires_at.is_some_and(|t| t <= now))
+            .map(|o| o.id)
+            .collect();
+
+        let evicted: Vec<Order> = expired_ids
+            .into_iter()
+            .filter_map(|id| self.cancel_order_inner(id))
+            .map(|(order, side, price)| {
+                log::info!("Reaped expired order {} ({:?} @ {})", order.id, side, price);
+                order
+            })
+            .collect();
+
+        if !evicted.is_empty() {
+            log::info!("Reaper evicted {} expired order(s) from {}", evicted.len(), self.symbol);
+        }
+        evicted
     }
 }
 
@@ -384,6 +1094,31 @@ mod tests {
     #[test] fn test_add_invalid_order_rejected() { /* ... */ }
     #[test] fn test_get_bbo_with_qty_logic() { /* ... */ }
     #[test] fn test_get_snapshot() { /* ... */ }
+    #[test] fn test_cancel_order_removes_resting_order() { /* ... */ }
+    #[test] fn test_cancel_order_unknown_id_returns_none() { /* ... */ }
+    #[test] fn test_amend_order_price_change_loses_priority() { /* ... */ }
+    #[test] fn test_amend_order_quantity_reduction_keeps_priority() { /* ... */ }
+    #[test] fn test_market_order_sweeps_book_ignoring_price_cap() { /* ... */ }
+    #[test] fn test_ioc_order_discards_unfilled_remainder() { /* ... */ }
+    #[test] fn test_fok_order_rejected_when_insufficient_liquidity() { /* ... */ }
+    #[test] fn test_fok_order_fills_fully_when_liquidity_sufficient() { /* ... */ }
+    #[test] fn test_with_config_rejects_sub_tick_price() { /* ... */ }
+    #[test] fn test_with_config_rejects_sub_lot_quantity() { /* ... */ }
+    #[test] fn test_with_config_rejects_below_min_size() { /* ... */ }
+    #[test] fn test_amend_order_rejected_by_market_config() { /* ... */ }
+    #[test] fn test_add_order_emits_delta_for_resting_level() { /* ... */ }
+    #[test] fn test_add_order_emits_delta_for_cleared_maker_level() { /* ... */ }
+    #[test] fn test_cancel_order_emits_zero_quantity_delta_when_level_empties() { /* ... */ }
+    #[test] fn test_checkpoint_returns_current_seq_and_full_snapshot() { /* ... */ }
+    #[test] fn test_update_oracle_repegs_resting_order_losing_priority() { /* ... */ }
+    #[test] fn test_update_oracle_parks_order_priced_non_positive() { /* ... */ }
+    #[test] fn test_update_oracle_reprices_parked_order_back_onto_book() { /* ... */ }
+    #[test] fn test_update_oracle_matches_repegged_order_that_now_crosses() { /* ... */ }
+    #[test] fn test_add_order_rejects_already_expired_order() { /* ... */ }
+    #[test] fn test_reap_expired_evicts_only_past_expiry_orders() { /* ... */ }
+    #[test] fn test_reap_expired_drops_now_empty_price_level() { /* ... */ }
+    #[test] fn test_reap_expired_returns_empty_when_nothing_expired() { /* ... */ }
+    #[test] fn test_next_bbo_seq_and_next_snapshot_seq_increment_independently() { /* ... */ }
     #[test]
     fn test_clear_book() {
         setup_logging();